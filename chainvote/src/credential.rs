@@ -0,0 +1,280 @@
+// Anonymous eligibility credentials with per-ballot nullifiers, modeled on CL-signature
+// anonymous credentials (libbolt). A voter's identity is never stored on-chain: the
+// registration authority only ever sees a Pedersen commitment to the voter's secret, and a
+// voter proves eligibility at vote time via a Chaum-Pedersen zero-knowledge proof of knowledge
+// of that commitment's opening `(s, r)`, bound to a nullifier that is the image of the same `s`
+// under a ballot-specific generator. Neither `s` nor `r` is ever transmitted, and the proof
+// itself is freshly blinded on every call, so presenting eligibility for the same ballot twice
+// produces two unlinkable transcripts.
+//
+// A production system would use a pairing-based CL/BBS+ signature so even the authority-signed
+// `commitment` could be re-randomized per presentation. To avoid an elliptic-curve dependency in
+// this repo, this module instead uses a Schnorr-style signature over a small prime-order group,
+// which is enough to demonstrate the commit/sign/prove/verify/nullifier flow end to end. Here
+// `commitment` is resent as-is each vote; only the proof of its opening and the nullifier are
+// zero-knowledge and presentation-unlinkable.
+
+/// A nullifier: a value published once per (credential, ballot) pair to block double voting.
+/// `h_ballot(ballot_id) ^ s mod MODULUS`, so it is verifiable against a voter's proof of
+/// knowledge of `s` without the verifier ever learning `s` itself.
+pub type Nullifier = u64;
+
+/// Modulus of the toy prime-order group this module computes in (2^31 - 1, a Mersenne prime).
+const MODULUS: u64 = 2_147_483_647;
+/// Generator used for the committed secret.
+const GENERATOR: u64 = 7;
+/// Second, independent generator used for the Pedersen commitment's blinding factor.
+const BLIND_GENERATOR: u64 = 11;
+
+fn mod_pow(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1u64;
+    base %= modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = ((result as u128 * base as u128) % modulus as u128) as u64;
+        }
+        exponent >>= 1;
+        base = ((base as u128 * base as u128) % modulus as u128) as u64;
+    }
+    result
+}
+
+/// A small, non-cryptographic mixing function used to derive Schnorr challenges and nullifier
+/// bytes deterministically (RFC 6979-style), so signing needs no randomness source; proving
+/// mixes in a caller-supplied `nonce` (see `Credential::prove`).
+fn fnv1a(values: &[u64]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for value in values {
+        for byte in value.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// A Pedersen commitment `C = g^s * h^r mod MODULUS` to a voter's secret `s`, blinded by `r`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Commitment(pub u64);
+
+/// Computes the commitment to secret `s` with blinding factor `r`.
+pub fn commit(s: u64, r: u64) -> Commitment {
+    let value = (mod_pow(GENERATOR, s, MODULUS) as u128 * mod_pow(BLIND_GENERATOR, r, MODULUS) as u128)
+        % MODULUS as u128;
+    Commitment(value as u64)
+}
+
+/// A Schnorr-style signature over a commitment.
+#[derive(Clone, Copy, Debug)]
+pub struct Signature {
+    r: u64,
+    z: u64,
+}
+
+/// The registration authority's keypair. Only the authority ever holds `secret_key`; everyone
+/// else, including the contract, only sees `public_key`.
+pub struct AuthorityKeyPair {
+    pub public_key: u64,
+    secret_key: u64,
+}
+
+impl AuthorityKeyPair {
+    pub fn generate(secret_key: u64) -> Self {
+        // Reduce mod (MODULUS - 1) up front (the order of the group `GENERATOR` generates), so
+        // `secret_key` can never be large enough to overflow the arithmetic `sign` does with it.
+        let secret_key = secret_key % (MODULUS - 1);
+        AuthorityKeyPair {
+            public_key: mod_pow(GENERATOR, secret_key, MODULUS),
+            secret_key,
+        }
+    }
+
+    /// Signs `commitment`, attesting that the voter behind it has been verified eligible,
+    /// without learning or storing who that voter is.
+    pub fn sign(&self, commitment: Commitment) -> Signature {
+        let nonce = fnv1a(&[commitment.0, self.secret_key]) % (MODULUS - 1) + 1;
+        let r = mod_pow(GENERATOR, nonce, MODULUS);
+        let challenge = fnv1a(&[r, commitment.0]) % (MODULUS - 1);
+        let z = ((nonce as u128 + challenge as u128 * self.secret_key as u128) % (MODULUS - 1) as u128) as u64;
+        Signature { r, z }
+    }
+}
+
+/// Checks that `signature` is a valid attestation by `public_key` over `commitment`.
+pub fn verify_signature(commitment: Commitment, signature: Signature, public_key: u64) -> bool {
+    let challenge = fnv1a(&[signature.r, commitment.0]) % (MODULUS - 1);
+    let lhs = mod_pow(GENERATOR, signature.z, MODULUS);
+    let rhs = (signature.r as u128 * mod_pow(public_key, challenge, MODULUS) as u128) % MODULUS as u128;
+    lhs as u128 == rhs
+}
+
+/// The credential a voter holds after registration: the secret opening `(s, r)` of the
+/// commitment the authority signed.
+pub struct Credential {
+    commitment: Commitment,
+    signature: Signature,
+    s: u64,
+    r: u64,
+}
+
+impl Credential {
+    pub fn new(s: u64, r: u64, signature: Signature) -> Self {
+        Credential {
+            commitment: commit(s, r),
+            signature,
+            s,
+            r,
+        }
+    }
+
+    /// Proves eligibility for `ballot_id` without revealing the committed opening `(s, r)`: a
+    /// Chaum-Pedersen-style proof of knowledge that the same `s` underlies both `self.commitment`
+    /// and the published nullifier, plus the authority's signature over `self.commitment`.
+    ///
+    /// `nonce` must be a fresh, unpredictable value the caller picks for every call (e.g. from a
+    /// local CSPRNG); it blinds the proof's nonce-commitments `t1`/`t2` so that proving eligibility
+    /// for the same ballot twice, or for two different ballots, never produces the same proof
+    /// transcript, even though `commitment` itself is unchanged across presentations.
+    pub fn prove(&self, ballot_id: u64, nonce: u64) -> EligibilityProof {
+        let h_ballot = ballot_generator(ballot_id);
+        let nullifier = mod_pow(h_ballot, self.s, MODULUS);
+
+        let k_s = fnv1a(&[self.s, self.r, ballot_id, nonce, 1]) % (MODULUS - 1);
+        let k_r = fnv1a(&[self.s, self.r, ballot_id, nonce, 2]) % (MODULUS - 1);
+        let t1 = (mod_pow(GENERATOR, k_s, MODULUS) as u128
+            * mod_pow(BLIND_GENERATOR, k_r, MODULUS) as u128
+            % MODULUS as u128) as u64;
+        let t2 = mod_pow(h_ballot, k_s, MODULUS);
+
+        let challenge = fnv1a(&[t1, t2, self.commitment.0, nullifier, ballot_id]) % (MODULUS - 1);
+        let z_s = ((k_s as u128 + challenge as u128 * self.s as u128) % (MODULUS - 1) as u128) as u64;
+        let z_r = ((k_r as u128 + challenge as u128 * self.r as u128) % (MODULUS - 1) as u128) as u64;
+
+        EligibilityProof {
+            commitment: self.commitment,
+            signature: self.signature,
+            nullifier,
+            t1,
+            t2,
+            z_s,
+            z_r,
+        }
+    }
+}
+
+/// A zero-knowledge proof of eligibility: the authority's signature over a committed credential,
+/// plus a Chaum-Pedersen proof of knowledge of that commitment's opening `(s, r)` which is also
+/// tied, via the same `s`, to `nullifier`. Neither `s` nor `r` appear anywhere in this struct.
+pub struct EligibilityProof {
+    pub commitment: Commitment,
+    pub signature: Signature,
+    pub nullifier: Nullifier,
+    /// Nonce-commitment for the `commitment = g^s * h^r` relation.
+    t1: u64,
+    /// Nonce-commitment for the `nullifier = h_ballot^s` relation.
+    t2: u64,
+    /// Combined response for the shared witness `s`.
+    z_s: u64,
+    /// Response for `r`, used only in the `commitment` relation.
+    z_r: u64,
+}
+
+/// Derives a generator unique to `ballot_id`, so that a credential's nullifier under one ballot
+/// cannot be related (without knowing `s`) to its nullifier under another.
+fn ballot_generator(ballot_id: u64) -> u64 {
+    let exponent = fnv1a(&[ballot_id]) % (MODULUS - 1);
+    mod_pow(GENERATOR, exponent, MODULUS)
+}
+
+/// Verifies `proof`: that the authority's signature over its commitment is valid, and that the
+/// proof demonstrates knowledge of an opening `(s, r)` of that commitment such that `nullifier`
+/// is `s`'s image under the generator for `ballot_id` — all without ever learning `s` or `r`.
+pub fn verify(proof: &EligibilityProof, authority_public_key: u64, ballot_id: u64) -> bool {
+    if !verify_signature(proof.commitment, proof.signature, authority_public_key) {
+        return false;
+    }
+
+    let h_ballot = ballot_generator(ballot_id);
+    let challenge = fnv1a(&[
+        proof.t1,
+        proof.t2,
+        proof.commitment.0,
+        proof.nullifier,
+        ballot_id,
+    ]) % (MODULUS - 1);
+
+    let lhs1 = (mod_pow(GENERATOR, proof.z_s, MODULUS) as u128
+        * mod_pow(BLIND_GENERATOR, proof.z_r, MODULUS) as u128
+        % MODULUS as u128) as u64;
+    let rhs1 = (proof.t1 as u128 * mod_pow(proof.commitment.0, challenge, MODULUS) as u128
+        % MODULUS as u128) as u64;
+    if lhs1 != rhs1 {
+        return false;
+    }
+
+    let lhs2 = mod_pow(h_ballot, proof.z_s, MODULUS);
+    let rhs2 =
+        (proof.t2 as u128 * mod_pow(proof.nullifier, challenge, MODULUS) as u128 % MODULUS as u128) as u64;
+    lhs2 == rhs2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_round_trips_through_commitment() {
+        let authority = AuthorityKeyPair::generate(12345);
+        let commitment = commit(42, 7);
+        let signature = authority.sign(commitment);
+        assert!(verify_signature(commitment, signature, authority.public_key));
+    }
+
+    #[test]
+    fn proof_verifies_against_matching_ballot_only() {
+        let authority = AuthorityKeyPair::generate(12345);
+        let commitment = commit(42, 7);
+        let signature = authority.sign(commitment);
+        let credential = Credential::new(42, 7, signature);
+
+        let proof = credential.prove(1, 999);
+        assert!(verify(&proof, authority.public_key, 1));
+        assert!(!verify(&proof, authority.public_key, 2));
+    }
+
+    #[test]
+    fn same_credential_yields_different_nullifiers_per_ballot() {
+        let authority = AuthorityKeyPair::generate(12345);
+        let commitment = commit(42, 7);
+        let signature = authority.sign(commitment);
+        let credential = Credential::new(42, 7, signature);
+
+        assert_ne!(credential.prove(1, 1).nullifier, credential.prove(2, 1).nullifier);
+        assert_eq!(credential.prove(1, 1).nullifier, credential.prove(1, 2).nullifier);
+    }
+
+    #[test]
+    fn proofs_for_the_same_ballot_are_unlinkable_across_presentations() {
+        // Two presentations of the same credential for the same ballot must not reuse the same
+        // proof transcript, or an observer could link them by equality just like the old
+        // raw-witness design did.
+        let authority = AuthorityKeyPair::generate(12345);
+        let commitment = commit(42, 7);
+        let signature = authority.sign(commitment);
+        let credential = Credential::new(42, 7, signature);
+
+        let first = credential.prove(1, 111);
+        let second = credential.prove(1, 222);
+        assert!(verify(&first, authority.public_key, 1));
+        assert!(verify(&second, authority.public_key, 1));
+        assert_ne!((first.t1, first.t2, first.z_s, first.z_r), (second.t1, second.t2, second.z_s, second.z_r));
+    }
+
+    #[test]
+    fn signing_does_not_overflow_with_large_secret_keys() {
+        let authority = AuthorityKeyPair::generate(u64::MAX - 1);
+        let commitment = commit(42, 7);
+        let signature = authority.sign(commitment);
+        assert!(verify_signature(commitment, signature, authority.public_key));
+    }
+}
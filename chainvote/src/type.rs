@@ -1,13 +1,20 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 use num_traits::FromPrimitive;
 
-// Traits for voter eligibility and verification
+mod credential;
+mod votes;
+use credential::{AuthorityKeyPair, EligibilityProof, Nullifier};
+use votes::Votes;
+
+// Trait for the authority-side eligibility criteria checked during off-chain registration.
+// Passing this check is what earns a voter a signed credential; nothing it examines is ever
+// stored on-chain.
 trait Eligible {
     fn is_eligible(&self) -> bool;
-    fn verify_identity(&self, id: &str) -> bool;
 }
 
-// Concrete implementation for eligible voters
+// The real-world identity details an authority checks before issuing a credential. Kept only
+// for the duration of the registration call; `VotingContract` never stores one of these.
 struct RegisteredVoter {
     name: String,
     age: u32,
@@ -18,11 +25,6 @@ impl Eligible for RegisteredVoter {
     fn is_eligible(&self) -> bool {
         self.age >= 18 && self.location == "EligibleRegion"
     }
-
-    fn verify_identity(&self, id: &str) -> bool {
-        // Implement secure identity verification (e.g., using cryptographic signatures)
-        id == "valid_id"
-    }
 }
 
 // Structure for candidates and their votes
@@ -31,50 +33,208 @@ struct Candidate {
     votes: u32,
 }
 
+// The kind of proposal a ballot decides, analogous to the ballot types used by POA governance
+// contracts. Each variant carries the data `finalize_ballot` needs to apply its outcome.
+enum BallotType {
+    AddCandidate(String),
+    RemoveCandidate(String),
+    ChangeMinThreshold(u32),
+    ChangeVotingPeriod { start_date: u64, end_date: u64 },
+    InvalidBallot,
+}
+
+// The payload an RPC-submitted ballot carries alongside its shortname discriminant. A bare `u64`
+// discriminant can't hold a candidate name or a date range, so the two travel together.
+enum BallotPayload {
+    Candidate(String),
+    MinThreshold(u32),
+    VotingPeriod { start_date: u64, end_date: u64 },
+    None,
+}
+
+impl BallotType {
+    // Discriminants mirror the RPC shortnames ballots are submitted under, so a ballot's type
+    // can be reconstructed from the `u64` tag sent over the wire together with its payload. A
+    // discriminant paired with the wrong payload variant (or an unrecognized discriminant)
+    // yields `InvalidBallot` rather than panicking.
+    fn from_rpc(discriminant: u64, payload: BallotPayload) -> Self {
+        match (discriminant, payload) {
+            (0, BallotPayload::Candidate(name)) => BallotType::AddCandidate(name),
+            (1, BallotPayload::Candidate(name)) => BallotType::RemoveCandidate(name),
+            (2, BallotPayload::MinThreshold(new_threshold)) => {
+                BallotType::ChangeMinThreshold(new_threshold)
+            }
+            (3, BallotPayload::VotingPeriod { start_date, end_date }) => {
+                BallotType::ChangeVotingPeriod { start_date, end_date }
+            }
+            _ => BallotType::InvalidBallot,
+        }
+    }
+}
+
+// A single proposal open for a yes/no vote, together with its tally so far. Double voting is
+// prevented contract-wide via nullifiers rather than per-ballot, since ballot identity is part
+// of what the nullifier itself commits to (see `credential::Credential::prove`).
+struct Ballot {
+    ballot_type: BallotType,
+    votes_for: u32,
+    votes_against: u32,
+}
+
+impl Ballot {
+    fn new(ballot_type: BallotType) -> Self {
+        Ballot {
+            ballot_type,
+            votes_for: 0,
+            votes_against: 0,
+        }
+    }
+}
+
 struct VotingContract {
     candidates: Vec<Candidate>,
-    eligible_voters: HashMap<String, RegisteredVoter>,
-    votes_cast: HashMap<String, u32>, // Voter ID -> Candidate index
+    delegation: Votes,  // Delegation and historical voting power
+    ballots: Vec<Ballot>,
+    active_ballot: Option<usize>, // Index into `ballots` of the ballot currently open for voting
+    min_threshold: u32,           // Minimum number of votes required for a ballot to pass
+    authority: AuthorityKeyPair,  // Issues credentials; only the authority ever learns `secret_key`
+    nullifiers: HashSet<Nullifier>, // Spent nullifiers, across all ballots
 }
 
 impl VotingContract {
-    fn new(candidates: Vec<Candidate>) -> Self {
+    fn new(candidates: Vec<Candidate>, authority_secret_key: u64) -> Self {
         VotingContract {
             candidates,
-            eligible_voters: HashMap::new(),
-            votes_cast: HashMap::new(),
+            delegation: Votes::new(),
+            ballots: Vec::new(),
+            active_ballot: None,
+            min_threshold: 1,
+            authority: AuthorityKeyPair::generate(authority_secret_key),
+            nullifiers: HashSet::new(),
         }
     }
 
-    fn register_voter(&mut self, voter: RegisteredVoter) {
-        if voter.is_eligible() {
-            self.eligible_voters.insert(voter.name, voter);
-        } else {
+    // Opens a new ballot for voting, replacing whichever ballot was previously active.
+    fn open_ballot(&mut self, ballot_type: BallotType) -> usize {
+        self.ballots.push(Ballot::new(ballot_type));
+        let ballot_id = self.ballots.len() - 1;
+        self.active_ballot = Some(ballot_id);
+        ballot_id
+    }
+
+    // RPC entry point: reconstructs the submitted ballot's type from its shortname `discriminant`
+    // and `payload`, then opens it.
+    fn submit_ballot(&mut self, discriminant: u64, payload: BallotPayload) -> usize {
+        self.open_ballot(BallotType::from_rpc(discriminant, payload))
+    }
+
+    /// Delegates `from`'s voting weight to `to`, checkpointed at `timepoint`.
+    fn delegate(&mut self, from: &str, to: &str, timepoint: u64) {
+        self.delegation.delegate(from, to, timepoint);
+    }
+
+    /// Returns `account`'s current voting power.
+    fn get_votes(&self, account: &str) -> u64 {
+        self.delegation.get_votes(account)
+    }
+
+    /// Returns `account`'s voting power as of `timepoint`.
+    fn get_past_votes(&self, account: &str, timepoint: u64) -> u64 {
+        self.delegation.get_past_votes(account, timepoint)
+    }
+
+    // Authority-side registration: checks `voter`'s eligibility (never stored) and, if
+    // eligible, signs `commitment` so the voter holds a credential usable in any future ballot
+    // without this contract ever learning who they are. `delegated_account` still identifies
+    // the voter for the unrelated delegation subsystem, which is not anonymous. `timepoint` is
+    // the caller-supplied current time, checkpointed as-is: `Trace::upper_lookup` panics on any
+    // query timepoint `>=` a voter's latest checkpoint, so hardcoding this to `0` would make
+    // `get_past_votes` unusable for every real timepoint until the voter's next `delegate` call.
+    fn register_voter(
+        &mut self,
+        voter: RegisteredVoter,
+        delegated_account: &str,
+        commitment: credential::Commitment,
+        timepoint: u64,
+    ) -> Option<credential::Signature> {
+        if !voter.is_eligible() {
             // Handle ineligible voter registration attempt
+            return None;
         }
+        self.delegation.set_units(delegated_account, 1, timepoint);
+        Some(self.authority.sign(commitment))
     }
 
-    fn cast_vote(&mut self, voter_id: &str, candidate_index: usize) -> bool {
-        if let Some(voter) = self.eligible_voters.get(voter_id) {
-            if voter.verify_identity(voter_id) {
-                if !self.votes_cast.contains_key(voter_id) {
-                    self.votes_cast.insert(voter_id.to_string(), candidate_index);
-                    self.candidates[candidate_index].votes += 1;
-                    true
+    // Casts a yes/no vote on whichever ballot is currently active, authenticated by an
+    // anonymous `EligibilityProof` rather than a plaintext identity. Dispatches on the ballot's
+    // type only insofar as an `InvalidBallot` can never be voted on; the actual outcome is
+    // applied later by `finalize_ballot`.
+    fn cast_vote(&mut self, proof: &EligibilityProof, in_favor: bool) -> bool {
+        let ballot_id = match self.active_ballot {
+            Some(ballot_id) => ballot_id,
+            None => return false, // Handle vote cast with no active ballot
+        };
+
+        if !credential::verify(proof, self.authority.public_key, ballot_id as u64) {
+            // Handle invalid or forged credential
+            return false;
+        }
+        if self.nullifiers.contains(&proof.nullifier) {
+            // Handle attempt to vote twice with the same credential
+            return false;
+        }
+
+        let ballot = &mut self.ballots[ballot_id];
+        match ballot.ballot_type {
+            BallotType::InvalidBallot => false, // Handle vote cast on an invalid ballot
+            _ => {
+                self.nullifiers.insert(proof.nullifier);
+                if in_favor {
+                    ballot.votes_for += 1;
                 } else {
-                    // Handle attempt to vote twice
-                    false
+                    ballot.votes_against += 1;
                 }
-            } else {
-                // Handle failed identity verification
-                false
+                true
             }
-        } else {
-            // Handle unregistered voter attempting to vote
-            false
         }
     }
 
+    // Applies the active ballot's outcome to the contract state, then closes the ballot. A
+    // ballot only passes if it met the minimum participation threshold and a majority voted for
+    // it.
+    fn finalize_ballot(&mut self) -> bool {
+        let ballot_id = match self.active_ballot.take() {
+            Some(ballot_id) => ballot_id,
+            None => return false,
+        };
+        let ballot = &self.ballots[ballot_id];
+        let total_votes = ballot.votes_for + ballot.votes_against;
+        let passed = total_votes >= self.min_threshold && ballot.votes_for > ballot.votes_against;
+        if !passed {
+            return false;
+        }
+
+        match &self.ballots[ballot_id].ballot_type {
+            BallotType::AddCandidate(name) => {
+                self.candidates.push(Candidate {
+                    name: name.clone(),
+                    votes: 0,
+                });
+            }
+            BallotType::RemoveCandidate(name) => {
+                self.candidates.retain(|c| &c.name != name);
+            }
+            BallotType::ChangeMinThreshold(new_threshold) => {
+                self.min_threshold = *new_threshold;
+            }
+            BallotType::ChangeVotingPeriod { .. } | BallotType::InvalidBallot => {
+                // VotingContract has no voting-period fields of its own; `Voting` (vote.rs)
+                // applies this variant to `start_date`/`end_date`.
+            }
+        }
+        true
+    }
+
     fn get_results(&self) -> Vec<String> {
         let mut results: Vec<String> = self.candidates.iter()
             .map(|c| format!("{}: {}", c.name, c.votes))
@@ -91,16 +251,30 @@ fn main() {
         Candidate { name: "Candidate A".to_string(), votes: 0 },
         Candidate { name: "Candidate B".to_string(), votes: 0 },
     ];
-    let mut contract = VotingContract::new(candidates);
-
-    // Register eligible voters (replace with actual voter registration process)
-    contract.register_voter(RegisteredVoter {
-        name: "John Doe".to_string(),
-        age: 25,
-        location: "EligibleRegion".to_string(),
-    });
-    contract.register_voter(RegisteredVoter {
-        name: "Jane Doe".to_string(),
-        age: 30,
-        location: "EligibleRegion".to_string(),
-    });
+    let mut contract = VotingContract::new(candidates, 12345);
+
+    // Register eligible voters (replace with actual voter registration process). Each voter
+    // picks their own secret `s` and blinding factor `r` and only sends the commitment on-chain.
+    contract.register_voter(
+        RegisteredVoter {
+            name: "John Doe".to_string(),
+            age: 25,
+            location: "EligibleRegion".to_string(),
+        },
+        "John Doe",
+        credential::commit(111, 222),
+        1,
+    );
+    contract.register_voter(
+        RegisteredVoter {
+            name: "Jane Doe".to_string(),
+            age: 30,
+            location: "EligibleRegion".to_string(),
+        },
+        "Jane Doe",
+        credential::commit(333, 444),
+        2,
+    );
+
+    // Submit a ballot the way an RPC caller would: a shortname discriminant plus its payload.
+    contract.submit_ballot(0, BallotPayload::Candidate("Candidate C".to_string()));
@@ -1,9 +1,3 @@
-// Define a struct to represent a voter
-struct Voter {
-    pub address: String, // Unique identifier for the voter
-    pub voted: bool, // Flag indicating if the voter has voted
-}
-
 // Define a struct to represent a candidate
 struct Candidate {
     pub name: String,
@@ -15,51 +9,101 @@ contract Voting {
     // List of candidates
     candidates: Vec<Candidate>,
 
-    // List of voters
-    voters: HashMap<String, Voter>,
-
     // Start and end date of the voting period
     start_date: u64,
     end_date: u64,
+
+    // Delegation and historical voting power, see `votes::Votes`
+    delegation: Votes,
+
+    // Proposals open for a yes/no vote, see `type::BallotType`
+    ballots: Vec<Ballot>,
+    active_ballot: Option<usize>,
+
+    // Issues anonymous credentials and tracks spent nullifiers, see `credential`. No per-voter
+    // identity is stored here, only the authority's own keypair.
+    authority: AuthorityKeyPair,
+    nullifiers: HashSet<Nullifier>,
 }
 
-// Function to register a voter
+// Function to delegate voting weight to another address
 impl Voting {
-    pub fn register(&mut self, address: String) -> bool {
-        if self.has_voted(&address) {
-            return false; // Prevent double voting
-        }
+    pub fn delegate(&mut self, from: String, to: String, timepoint: u64) {
+        self.delegation.delegate(&from, &to, timepoint);
+    }
 
+    pub fn get_votes(&self, account: &str) -> u64 {
+        self.delegation.get_votes(account)
+    }
+
+    pub fn get_past_votes(&self, account: &str, timepoint: u64) -> u64 {
+        self.delegation.get_past_votes(account, timepoint)
+    }
+}
+
+// Function to register a voter: signs a voter-supplied commitment once the voting period allows
+// it, without ever learning who the voter is.
+impl Voting {
+    pub fn register(&mut self, commitment: Commitment) -> Option<Signature> {
         if self.is_past_end_date() {
-            return false; // Can't register after voting period
+            return None; // Can't register after voting period
         }
 
-        self.voters.insert(address.clone(), Voter { address, voted: false });
-        true
+        Some(self.authority.sign(commitment))
     }
 }
 
-// Function to cast a vote for a candidate
+// Function to cast a vote for a candidate, authenticated by an anonymous eligibility proof
+// instead of a plaintext address.
 impl Voting {
-    pub fn vote(&mut self, address: String, candidate_index: usize) -> bool {
+    pub fn vote(&mut self, proof: &EligibilityProof, candidate_index: usize) -> bool {
         if !self.is_within_voting_period() {
             return false; // Can't vote outside voting period
         }
 
-        if self.has_voted(&address) {
-            return false; // Prevent double voting
-        }
-
         if candidate_index >= self.candidates.len() {
             return false; // Invalid candidate index
         }
 
-        self.voters.get_mut(&address).unwrap().voted = true;
+        // The candidate election is one ballot, so every proof is checked against the same ballot
+        // id (distinct from any index into `ballots`); a credential's nullifier is then the same
+        // whichever candidate it picks.
+        if !verify(proof, self.authority.public_key, u64::MAX) {
+            return false; // Invalid or forged credential
+        }
+
+        if self.nullifiers.contains(&proof.nullifier) {
+            return false; // Prevent double voting
+        }
+
+        self.nullifiers.insert(proof.nullifier);
         self.candidates[candidate_index].votes += 1;
         true
     }
 }
 
+// Applies a finished ballot's outcome. `ChangeVotingPeriod` is the one `BallotType` variant that
+// only makes sense here, since `VotingContract` (type.rs) has no voting-period fields of its own.
+impl Voting {
+    pub fn finalize_ballot(&mut self) -> bool {
+        let ballot_id = match self.active_ballot.take() {
+            Some(ballot_id) => ballot_id,
+            None => return false,
+        };
+        let ballot = &self.ballots[ballot_id];
+        let passed = ballot.votes_for > ballot.votes_against;
+        if !passed {
+            return false;
+        }
+
+        if let BallotType::ChangeVotingPeriod { start_date, end_date } = self.ballots[ballot_id].ballot_type {
+            self.start_date = start_date;
+            self.end_date = end_date;
+        }
+        true
+    }
+}
+
 // Helper functions
 impl Voting {
     fn is_within_voting_period(&self) -> bool {
@@ -67,10 +111,6 @@ impl Voting {
         current_time >= self.start_date && current_time <= self.end_date
     }
 
-    fn has_voted(&self, address: &str) -> bool {
-        self.voters.get(address).map(|voter| voter.voted).unwrap_or(false)
-    }
-
     fn is_past_end_date(&self) -> bool {
         let current_time = // Replace with actual time retrieval mechanism
         current_time > self.end_date
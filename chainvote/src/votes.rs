@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+// Voting-power delegation with historical checkpoints, modeled on OpenZeppelin's Votes
+// component. Every account's weight is tracked over time so past voting power can still be
+// queried after a delegation changes it.
+
+/// A single checkpoint recording the cumulative weight held by an account as of `key`.
+#[derive(Clone, Copy, Debug)]
+struct Checkpoint {
+    /// The point in time (block number or timestamp) the checkpoint was recorded at.
+    key: u64,
+    /// The cumulative weight held at and after `key`, until the next checkpoint.
+    value: u64,
+}
+
+/// A sorted history of checkpoints for a single account. Keys are strictly increasing.
+#[derive(Clone, Debug, Default)]
+struct Trace {
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl Trace {
+    /// Returns the value of the latest checkpoint, or 0 if none have been recorded.
+    fn latest(&self) -> u64 {
+        self.checkpoints.last().map(|c| c.value).unwrap_or(0)
+    }
+
+    /// Pushes a new checkpoint for `value` at `key`. If the latest checkpoint already has the
+    /// same key (i.e. two updates happened at the same point in time), it is overwritten in
+    /// place instead of creating a duplicate entry.
+    ///
+    /// Panics if `key` is before the latest checkpoint's key: keys must be strictly increasing
+    /// (equal is allowed and handled above) for `upper_lookup`'s binary search to be valid.
+    fn push(&mut self, key: u64, value: u64) {
+        match self.checkpoints.last_mut() {
+            Some(last) if last.key == key => last.value = value,
+            Some(last) => {
+                assert!(
+                    key > last.key,
+                    "Votes: checkpoint keys must be strictly increasing"
+                );
+                self.checkpoints.push(Checkpoint { key, value });
+            }
+            None => self.checkpoints.push(Checkpoint { key, value }),
+        }
+    }
+
+    /// Returns the value of the last checkpoint whose key is `<= timepoint`, or 0 if there is
+    /// none. Implemented as an upper-bound binary search over the sorted checkpoints.
+    ///
+    /// Panics if `timepoint` is at or after the most recent checkpoint's key, since querying the
+    /// current or a future point in time would allow mid-block manipulation of the result.
+    fn upper_lookup(&self, timepoint: u64) -> u64 {
+        if let Some(last) = self.checkpoints.last() {
+            assert!(
+                timepoint < last.key,
+                "Votes: future lookup, timepoint must be before the latest checkpoint"
+            );
+        }
+
+        let idx = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.key <= timepoint);
+        if idx == 0 {
+            0
+        } else {
+            self.checkpoints[idx - 1].value
+        }
+    }
+}
+
+/// Delegation and historical voting-power bookkeeping for a [`VotingContract`](crate::r#type::VotingContract).
+#[derive(Default)]
+pub struct Votes {
+    /// Each account's own voting weight (e.g. tokens held), independent of delegation.
+    units: HashMap<String, u64>,
+    /// Who each account currently delegates its weight to. An account with no entry delegates
+    /// to itself.
+    delegates: HashMap<String, String>,
+    /// Historical voting power per delegatee, as a checkpoint trace.
+    checkpoints: HashMap<String, Trace>,
+}
+
+impl Votes {
+    pub fn new() -> Self {
+        Votes::default()
+    }
+
+    /// Registers `account`'s own voting weight, defaulting its delegatee to itself.
+    pub fn set_units(&mut self, account: &str, units: u64, timepoint: u64) {
+        let old_units = self.units.insert(account.to_string(), units).unwrap_or(0);
+        let delegatee = self.delegatee_of(account);
+        if units >= old_units {
+            self.move_delegate_votes(None, Some(&delegatee), units - old_units, timepoint);
+        } else {
+            self.move_delegate_votes(Some(&delegatee), None, old_units - units, timepoint);
+        }
+    }
+
+    /// Returns who `account` currently delegates to (itself, if it has never delegated).
+    fn delegatee_of(&self, account: &str) -> String {
+        self.delegates
+            .get(account)
+            .cloned()
+            .unwrap_or_else(|| account.to_string())
+    }
+
+    /// Moves `from`'s voting units away from its current delegatee and onto `to`, checkpointing
+    /// the new cumulative weight for each delegatee at `timepoint`.
+    pub fn delegate(&mut self, from: &str, to: &str, timepoint: u64) {
+        let units = *self.units.get(from).unwrap_or(&0);
+        let old_delegatee = self.delegatee_of(from);
+        self.delegates.insert(from.to_string(), to.to_string());
+        self.move_delegate_votes(Some(&old_delegatee), Some(to), units, timepoint);
+    }
+
+    /// Subtracts `amount` from `from`'s checkpoint trace (if any) and adds it to `to`'s,
+    /// pushing a new checkpoint with the updated cumulative weight for whichever side changed.
+    fn move_delegate_votes(&mut self, from: Option<&str>, to: Option<&str>, amount: u64, timepoint: u64) {
+        if amount == 0 {
+            return;
+        }
+        if let Some(from) = from {
+            let trace = self.checkpoints.entry(from.to_string()).or_default();
+            let new_value = trace.latest().saturating_sub(amount);
+            trace.push(timepoint, new_value);
+        }
+        if let Some(to) = to {
+            let trace = self.checkpoints.entry(to.to_string()).or_default();
+            let new_value = trace.latest() + amount;
+            trace.push(timepoint, new_value);
+        }
+    }
+
+    /// Returns `account`'s current voting power.
+    pub fn get_votes(&self, account: &str) -> u64 {
+        self.checkpoints
+            .get(account)
+            .map(|trace| trace.latest())
+            .unwrap_or(0)
+    }
+
+    /// Returns `account`'s voting power as of `timepoint`, which must be strictly before the
+    /// account's latest checkpoint.
+    pub fn get_past_votes(&self, account: &str, timepoint: u64) -> u64 {
+        self.checkpoints
+            .get(account)
+            .map(|trace| trace.upper_lookup(timepoint))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delegation_moves_weight_and_checkpoints_history() {
+        let mut votes = Votes::new();
+        votes.set_units("alice", 100, 1);
+        assert_eq!(votes.get_votes("alice"), 100);
+
+        votes.delegate("alice", "bob", 2);
+        assert_eq!(votes.get_votes("alice"), 0);
+        assert_eq!(votes.get_votes("bob"), 100);
+
+        // Past lookups reflect the state before delegation.
+        assert_eq!(votes.get_past_votes("alice", 1), 100);
+        assert_eq!(votes.get_past_votes("bob", 1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "future lookup")]
+    fn get_past_votes_rejects_current_or_future_timepoint() {
+        let mut votes = Votes::new();
+        votes.set_units("alice", 10, 5);
+        votes.get_past_votes("alice", 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn set_units_rejects_out_of_order_timepoint() {
+        let mut votes = Votes::new();
+        votes.set_units("alice", 10, 5);
+        votes.set_units("alice", 20, 3);
+    }
+}
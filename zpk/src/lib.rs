@@ -1,16 +1,18 @@
-//! Simple secret sum contract.
+//! Private per-candidate tally contract.
 //!
-//! Calculates the sum of secret inputs from multiple parties. The inputs are not revealed.
+//! Calculates, for each candidate, the number of votes cast for them, without revealing any
+//! individual party's ballot. Each party's secret input is a one-hot vector over the candidates
+//! (one secret variable per candidate, exactly one of which is 1).
 //!
 //! This implementation works in following steps:
 //!
 //! 1. Initialization on the blockchain.
-//! 2. Receival of multiple secret inputs, using the real zk protocol.
+//! 2. Receival of multiple secret one-hot inputs, using the real zk protocol.
 //! 3. The contract owner can start the ZK computation.
-//! 4. The Zk computation sums all the given inputs.
-//! 5. Once the zk computation is complete, the contract will publicize the summed variable.
-//! 6. Once the summed variable is public, the contract will also store it in the state,
-//!     such that the value can be read by all.
+//! 4. The Zk computation sums each candidate's slot across all inputs.
+//! 5. Once the zk computation is complete, the contract will publicize the tally vector.
+//! 6. Once the tally vector is public, the contract will also store it in the state,
+//!     such that the results can be read by all.
 //!
 
 #![allow(unused_variables)]
@@ -20,6 +22,8 @@ extern crate pbc_contract_codegen;
 extern crate pbc_contract_common;
 extern crate pbc_lib;
 
+use std::collections::HashMap;
+
 use pbc_contract_common::address::Address;
 use pbc_contract_common::context::ContractContext;
 use pbc_contract_common::events::EventGroup;
@@ -34,19 +38,59 @@ struct SecretVarMetadata {}
 /// The maximum size of MPC variables.
 const BITLENGTH_OF_SECRET_VARIABLES: u32 = 32;
 
+/// Exclusive upper bound every secret value slot must lie within. Mirrors the `BOUND` used by
+/// `zk_compute`'s digit-decomposition range check; kept here so the RPC-facing contract and the
+/// in-circuit computation agree on the same constant.
+const BOUND: i32 = 2;
+
+/// Number of base-2 digits needed to cover [`BOUND`].
+const BOUND_BITS: usize = 1;
+
+/// The number of candidates in the election. Each party's secret input consists of this many
+/// one-hot value slots, followed by all of their [`BOUND_BITS`]-digit decompositions, grouped
+/// value-then-digit-blocks (not interleaved per slot): `v0, v1, ..., d0, d1, ...`.
+const NUM_CANDIDATES: usize = 3;
+
 /// The contract's state
 ///
 /// ### Fields:
 ///
 /// * `administrator`: [`Address`], the administrator of the contract.
 ///
-/// * `sum_result`: [`Option<u32>`], place for storing the final result of the zk computation.
+/// * `tally`: [`Option<Vec<u32>>`], place for storing the final per-candidate tally of the zk
+/// computation.
+///
+/// * `eligible_voters`: [`Vec<Address>`], voters allowed to participate in the current ballot.
+///
+/// * `votes_cast`: [`Vec<Address>`], voters who have submitted a secret input in the current
+/// ballot, i.e. the participants.
+///
+/// * `missed_ballots`: [`HashMap<Address, u32>`], a rolling count of how many ballots each voter
+/// has abstained from, across the contract's lifetime.
+///
+/// * `rejected_count`: [`Option<u32>`], the number of ballots rejected in the most recently
+/// completed computation, because a value slot failed the `[0, BOUND)` range check or the slots
+/// did not sum to 1.
+///
+/// * `ranking`: [`Option<Vec<usize>>`], candidate indices sorted from most to least votes,
+/// derived from `tally`.
 #[state]
 struct ContractState {
     /// Address allowed to start computation
     administrator: Address,
-    /// Will contain the result (sum) when computation is complete
-    sum_result: Option<u32>,
+    /// Will contain the per-candidate tally when computation is complete, ordered by candidate
+    /// index.
+    tally: Option<Vec<u32>>,
+    /// Voters allowed to participate in the current ballot
+    eligible_voters: Vec<Address>,
+    /// Voters who have submitted a secret input in the current ballot
+    votes_cast: Vec<Address>,
+    /// Number of ballots each voter has abstained from, keyed by voter address
+    missed_ballots: HashMap<Address, u32>,
+    /// Number of ballots rejected by the most recently completed computation
+    rejected_count: Option<u32>,
+    /// Candidate indices ranked from most to least votes, once `tally` is known
+    ranking: Option<Vec<usize>>,
 }
 
 /// Initializes the contract and bootstrab the contract state.
@@ -65,11 +109,48 @@ struct ContractState {
 fn initialize(ctx: ContractContext, zk_state: ZkState<SecretVarMetadata>) -> ContractState {
     ContractState {
         administrator: ctx.sender,
-        sum_result: None,
+        tally: None,
+        eligible_voters: vec![],
+        votes_cast: vec![],
+        missed_ballots: HashMap::new(),
+        rejected_count: None,
+        ranking: None,
+    }
+}
+
+/// Registers `voter` as eligible to participate in the current and future ballots. Only
+/// callable by the administrator.
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the current call.
+///
+/// * `state`: [`ContractState`], the current state of the contract.
+///
+/// * `voter`: [`Address`], the voter to register as eligible.
+///
+/// ### Returns
+///
+/// The state with `voter` added to `eligible_voters`.
+#[action(shortname = 0x02)]
+fn add_eligible_voter(context: ContractContext, mut state: ContractState, voter: Address) -> ContractState {
+    assert_eq!(
+        context.sender, state.administrator,
+        "Only administrator can register eligible voters"
+    );
+    if !state.eligible_voters.contains(&voter) {
+        state.eligible_voters.push(voter);
     }
+    state
 }
 
-/// Adds another secret input of size [`BITLENGTH_OF_SECRET_VARIABLES`].
+/// Adds another secret ballot: a one-hot vector of [`NUM_CANDIDATES`] value slots, where exactly
+/// one slot is 1 (the chosen candidate) and the rest are 0, followed by all of their
+/// [`BOUND_BITS`]-digit base-2 decompositions (value slots first, then digit slots in the same
+/// candidate order — not interleaved) so `zk_compute` can range-check each value against
+/// [`BOUND`] without revealing it. All variables are [`BITLENGTH_OF_SECRET_VARIABLES`] bits.
+/// Only callable by a registered `eligible_voters` address, so turnout/abstention statistics
+/// stay accurate.
 ///
 /// ### Parameters:
 ///
@@ -81,21 +162,32 @@ fn initialize(ctx: ContractContext, zk_state: ZkState<SecretVarMetadata>) -> Con
 ///
 /// ### Returns
 ///
-/// The unchanged state, and a ZkInputDef defining the input size.
+/// The state with the sender recorded in `votes_cast`, and a ZkInputDef defining the one-hot
+/// input.
 #[zk_on_secret_input(shortname = 0x40)]
 fn add_input(
     context: ContractContext,
-    state: ContractState,
+    mut state: ContractState,
     zk_state: ZkState<SecretVarMetadata>,
 ) -> (
     ContractState,
     Vec<EventGroup>,
     ZkInputDef<SecretVarMetadata>,
 ) {
+    assert!(
+        state.eligible_voters.contains(&context.sender),
+        "Only eligible voters can submit a secret ballot"
+    );
+    if !state.votes_cast.contains(&context.sender) {
+        state.votes_cast.push(context.sender);
+    }
     let input_def = ZkInputDef {
         seal: false,
         metadata: SecretVarMetadata {},
-        expected_bit_lengths: vec![BITLENGTH_OF_SECRET_VARIABLES],
+        expected_bit_lengths: vec![
+            BITLENGTH_OF_SECRET_VARIABLES;
+            NUM_CANDIDATES + NUM_CANDIDATES * BOUND_BITS
+        ],
     };
     (state, vec![], input_def)
 }
@@ -126,8 +218,8 @@ fn inputted_variable(
     state
 }
 
-/// Start the zk-computation computing the sum of the secret variables. Only callable by the
-/// administrator.
+/// Start the zk-computation computing the per-candidate tally of the secret ballots. Only
+/// callable by the administrator.
 ///
 /// ### Parameters:
 ///
@@ -160,7 +252,10 @@ fn compute_sum(
     (
         state,
         vec![],
-        vec![ZkStateChange::start_computation(vec![SecretVarMetadata {}])],
+        vec![ZkStateChange::start_computation(vec![
+            SecretVarMetadata {};
+            NUM_CANDIDATES + 1
+        ])],
     )
 }
 
@@ -199,7 +294,9 @@ fn sum_compute_complete(
 
 /// Automatically called when a variable is opened/declassified.
 ///
-/// We can now read the sum variable, and save it in the contract state.
+/// We can now read the per-candidate tally, rank the candidates by vote count, and save the
+/// tally in the contract state. Individual ballots remain secret; only the aggregate counts are
+/// made public here.
 ///
 /// ### Parameters:
 ///
@@ -213,7 +310,9 @@ fn sum_compute_complete(
 ///
 /// ### Returns
 ///
-/// The new state with the computed sum, and a ZkStateChange denoting that the zk computation is done.
+/// The new state with the computed tally, its derived candidate ranking, and updated
+/// participation statistics, and a ZkStateChange denoting that the zk computation is done, plus
+/// an EventGroup reporting turnout and abstainers for this ballot.
 #[zk_on_variables_opened]
 fn open_sum_variable(
     context: ContractContext,
@@ -223,12 +322,74 @@ fn open_sum_variable(
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
     assert_eq!(
         opened_variables.len(),
-        1,
+        NUM_CANDIDATES + 1,
         "Unexpected number of output variables"
     );
-    let sum = read_variable_u32_le(&zk_state, opened_variables.get(0));
-    state.sum_result = Some(sum);
-    (state, vec![], vec![ZkStateChange::ContractDone])
+    let tally: Vec<u32> = opened_variables[..NUM_CANDIDATES]
+        .iter()
+        .map(|id| read_variable_u32_le(&zk_state, Some(id)))
+        .collect();
+    state.ranking = Some(rank_candidates(&tally));
+    state.tally = Some(tally);
+    state.rejected_count = Some(read_variable_u32_le(
+        &zk_state,
+        opened_variables.get(NUM_CANDIDATES),
+    ));
+
+    let event_group = emit_ballot_stats(&mut state);
+
+    (state, vec![event_group], vec![ZkStateChange::ContractDone])
+}
+
+/// Diffs `eligible_voters` against `votes_cast` to find this ballot's abstainers, records a
+/// missed ballot for each of them, and builds an [`EventGroup`] reporting turnout and the
+/// abstainer list for off-chain indexers. Resets `votes_cast` so the next ballot starts fresh.
+///
+/// ### Parameters:
+///
+/// * `state`: [`&mut ContractState`], the contract state, updated with the new missed-ballot
+/// counts.
+///
+/// ### Returns
+/// An [`EventGroup`] containing the turnout and abstainer list for this ballot.
+fn emit_ballot_stats(state: &mut ContractState) -> EventGroup {
+    let abstainers: Vec<Address> = state
+        .eligible_voters
+        .iter()
+        .filter(|voter| !state.votes_cast.contains(voter))
+        .cloned()
+        .collect();
+
+    for voter in &abstainers {
+        *state.missed_ballots.entry(*voter).or_insert(0) += 1;
+    }
+
+    let turnout = state.votes_cast.len() as u32;
+    let mut event_group_builder = EventGroup::builder();
+    event_group_builder
+        .call(state.administrator, pbc_contract_common::shortname::Shortname::from_u32(0))
+        .argument(turnout)
+        .argument(abstainers)
+        .done();
+    let event_group = event_group_builder.build();
+
+    state.votes_cast = vec![];
+
+    event_group
+}
+
+/// Ranks candidates by their tally, from most to least votes.
+///
+/// ### Parameters:
+///
+/// * `tally`: [`&[u32]`], the per-candidate vote counts, indexed by candidate.
+///
+/// ### Returns
+/// A list of candidate indices sorted in descending order of votes received.
+fn rank_candidates(tally: &[u32]) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..tally.len()).collect();
+    ranked.sort_by(|&a, &b| tally[b].cmp(&tally[a]));
+    ranked
 }
 
 /// Reads a variable's data as an u32.
@@ -1,13 +1,136 @@
-/// Template zk computation. Computes the sum of the secret variables.
+/// Template zk computation. Computes the per-candidate tally of the secret inputs.
+///
+/// Each party contributes a one-hot vector of [`NUM_CANDIDATES`] secret variables, where the
+/// variable at candidate index `i` is 1 if the party voted for candidate `i` and 0 otherwise.
+/// Since nothing on-chain otherwise stops a malicious party from submitting an out-of-range
+/// value (e.g. a huge integer that would corrupt the tally once summed), each slot is
+/// accompanied by its own base-2 digit decomposition so the circuit can enforce
+/// `0 <= value < BOUND` without ever revealing `value` itself. Per-slot range checks alone don't
+/// stop a party from submitting e.g. `[1, 1, 1]` to vote for every candidate at once, so the
+/// circuit additionally checks that a party's slots sum to exactly 1. A ballot that fails either
+/// check contributes 0 to the tally and bumps the public `rejected_count` instead.
 use pbc_zk::*;
 
-pub fn zk_compute() -> Sbi32 {
-      let mut sum: Sbi32 = sbi32_from(0);
+/// The number of candidates in the election. Must match the number of value slots each party
+/// submits per input.
+const NUM_CANDIDATES: u32 = 3;
 
-      // Sum each variable
-      for variable_id in 1..(num_secret_variables() + 1) {
-          sum = sum + sbi32_input(variable_id);
-      }
+/// Exclusive upper bound every secret value slot must satisfy. MPC has no cheap native
+/// less-than for secret values, so this is enforced via digit decomposition instead.
+const BOUND: i32 = 2;
 
-      sum
+/// Number of base-2 digits needed to cover [`BOUND`] (`2^BOUND_BITS >= BOUND`).
+const BOUND_BITS: u32 = 1;
+
+/// Number of secret variables a single party submits: one value per candidate, plus that
+/// value's digit decomposition.
+const VARIABLES_PER_PARTY: u32 = NUM_CANDIDATES + NUM_CANDIDATES * BOUND_BITS;
+
+pub fn zk_compute() -> ([Sbi32; NUM_CANDIDATES as usize], Sbi32) {
+    let mut tally: [Sbi32; NUM_CANDIDATES as usize] = [sbi32_from(0); NUM_CANDIDATES as usize];
+    let mut rejected_count: Sbi32 = sbi32_from(0);
+
+    let num_parties = num_secret_variables() / VARIABLES_PER_PARTY;
+    for party in 0..num_parties {
+        let block_start = party * VARIABLES_PER_PARTY;
+        let mut values: [Sbi32; NUM_CANDIDATES as usize] = [sbi32_from(0); NUM_CANDIDATES as usize];
+        let mut one_hot = sbi32_from(1);
+        let mut sum = sbi32_from(0);
+        for candidate in 0..NUM_CANDIDATES {
+            let value_id = block_start + candidate + 1;
+            let value = sbi32_input(value_id);
+            values[candidate as usize] = value;
+            sum = sum + value;
+
+            let digits_start = block_start + NUM_CANDIDATES + candidate * BOUND_BITS + 1;
+            let mut digits: [Sbi32; BOUND_BITS as usize] = [sbi32_from(0); BOUND_BITS as usize];
+            for digit_index in 0..BOUND_BITS {
+                digits[digit_index as usize] = sbi32_input(digits_start + digit_index);
+            }
+
+            one_hot = one_hot * range_check(value, &digits);
+        }
+        // A valid ballot has exactly one candidate slot set to 1 and the rest 0, i.e. the slots
+        // sum to exactly 1. Without this, each per-slot range check of `[0, BOUND)` passes for
+        // e.g. `[1, 1, 1]`, letting one ballot count as a vote for every candidate at once.
+        one_hot = one_hot * is_zero(sbi32_from(1) - sum);
+
+        for candidate in 0..NUM_CANDIDATES {
+            tally[candidate as usize] = tally[candidate as usize] + values[candidate as usize] * one_hot;
+        }
+        rejected_count = rejected_count + (sbi32_from(1) - one_hot);
+    }
+
+    (tally, rejected_count)
+}
+
+/// Checks that `value` is the number represented by `digits` (least-significant first), that
+/// every digit is boolean, and that the reconstructed number is `< BOUND`.
+///
+/// ### Returns
+/// `1` if all three checks pass, `0` otherwise. Each check is itself a secret 0/1 flag, and the
+/// checks are combined with multiplication, which is logical AND for 0/1 values.
+fn range_check(value: Sbi32, digits: &[Sbi32]) -> Sbi32 {
+    let mut reconstructed = sbi32_from(0);
+    let mut place_value = sbi32_from(1);
+    for &digit in digits {
+        reconstructed = reconstructed + digit * place_value;
+        place_value = place_value + place_value;
+    }
+
+    let mut valid = is_zero(value - reconstructed);
+    for &digit in digits {
+        valid = valid * is_boolean(digit);
+    }
+    valid = valid * less_than_bound(digits);
+    valid
+}
+
+/// Returns `1` if `v == 0`, `0` otherwise.
+fn is_zero(v: Sbi32) -> Sbi32 {
+    if v == sbi32_from(0) {
+        sbi32_from(1)
+    } else {
+        sbi32_from(0)
+    }
+}
+
+/// Returns `1` if `d` is `0` or `1`, `0` otherwise, via `d * (d - 1) == 0`.
+fn is_boolean(d: Sbi32) -> Sbi32 {
+    is_zero(d * (d - sbi32_from(1)))
+}
+
+/// Returns `1` if the number represented by `digits` (least-significant first) is strictly less
+/// than [`BOUND`], `0` otherwise.
+///
+/// Compares against `BOUND - 1` with `<=` semantics rather than truncating `BOUND`'s own bits:
+/// `BOUND` itself does not generally fit in `digits.len()` bits (e.g. when `BOUND` is a power of
+/// two, as it is by default), so comparing against it directly would silently drop its top bit
+/// and reject every input. `BOUND - 1` always fits, since `digits.len()` was chosen so that
+/// `2^digits.len() >= BOUND`.
+///
+/// Walks the digits from most- to least-significant, tracking a "still equal to the bound's
+/// prefix so far" flag; the comparison resolves to greater-than at the first digit where
+/// `digits` has a `1` where `BOUND - 1` has a `0`, while every more-significant digit still
+/// matched. This runs in a constant number of rounds regardless of which digit decides the
+/// comparison.
+fn less_than_bound(digits: &[Sbi32]) -> Sbi32 {
+    let mut remaining = BOUND - 1;
+    let mut bound_minus_one_digits: Vec<Sbi32> = Vec::with_capacity(digits.len());
+    for _ in 0..digits.len() {
+        bound_minus_one_digits.push(sbi32_from(remaining & 1));
+        remaining >>= 1;
+    }
+
+    let mut still_equal_prefix = sbi32_from(1);
+    let mut greater = sbi32_from(0);
+    for i in (0..digits.len()).rev() {
+        let digit = digits[i];
+        let bound_digit = bound_minus_one_digits[i];
+        let digit_is_greater = digit * (sbi32_from(1) - bound_digit);
+        greater = greater + still_equal_prefix * digit_is_greater;
+        still_equal_prefix = still_equal_prefix * is_zero(digit - bound_digit);
+    }
+    // value <= BOUND - 1, i.e. value < BOUND, iff it is not greater than BOUND - 1.
+    sbi32_from(1) - greater
 }